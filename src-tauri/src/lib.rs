@@ -1,11 +1,174 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
 use std::sync::Mutex;
+use futures_util::StreamExt;
+use base64::Engine;
 
 // State to track if overlay is currently visible
 struct OverlayState {
     is_visible: Mutex<bool>,
+    is_click_through: Mutex<bool>,
+    visible_on_all_workspaces: Mutex<bool>,
+}
+
+// State holding the running multi-turn conversation sent to Gemini, so the
+// overlay keeps context across `send_to_ai` calls instead of starting fresh
+// each time.
+struct ConversationState {
+    history: Mutex<Vec<GeminiContent>>,
+    // Bumped by `clear_conversation`. A stream only appends its reply if this
+    // hasn't changed since it started, so a clear fired mid-stream can't have
+    // the in-flight turn reappear once the response finally comes back.
+    generation: Mutex<u64>,
+}
+
+// Named actions the user can bind a global shortcut to.
+const SHORTCUT_ACTIONS: [&str; 4] = ["toggle_overlay", "toggle_click_through", "hide", "new_conversation"];
+
+// State holding the current action -> accelerator bindings, kept in sync
+// with the `global_shortcut` plugin's registrations and with the bindings
+// persisted at `shortcuts_file_path`.
+struct ShortcutState {
+    bindings: Mutex<HashMap<String, String>>,
+}
+
+fn default_shortcuts() -> HashMap<String, String> {
+    HashMap::from([
+        ("toggle_overlay".to_string(), "Ctrl+Space".to_string()),
+        ("toggle_click_through".to_string(), "Ctrl+Shift+C".to_string()),
+        ("hide".to_string(), "Ctrl+Shift+H".to_string()),
+        ("new_conversation".to_string(), "Ctrl+Shift+N".to_string()),
+    ])
+}
+
+fn shortcuts_file_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("shortcuts.json"))
+}
+
+// Loads persisted bindings, falling back to the defaults if the settings
+// file is missing or unreadable.
+fn load_shortcuts(app: &tauri::AppHandle) -> HashMap<String, String> {
+    shortcuts_file_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_else(default_shortcuts)
+}
+
+fn save_shortcuts(app: &tauri::AppHandle, bindings: &HashMap<String, String>) -> Result<(), String> {
+    let path = shortcuts_file_path(app)?;
+    let json = serde_json::to_string_pretty(bindings).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+// Validates an accelerator string the way the `global_shortcut` plugin will
+// parse it, so bad input is rejected before we try to register it.
+fn parse_accelerator(accelerator: &str) -> Result<tauri_plugin_global_shortcut::Shortcut, String> {
+    accelerator
+        .parse::<tauri_plugin_global_shortcut::Shortcut>()
+        .map_err(|e| format!("Invalid accelerator \"{}\": {}", accelerator, e))
+}
+
+// Unregisters every shortcut currently held by the plugin, then registers
+// each binding in `bindings`. Invalid accelerators are skipped rather than
+// aborting the whole batch, since they shouldn't be able to happen once
+// `set_shortcut` is the only writer of the settings file.
+fn register_all_shortcuts(app: &tauri::AppHandle, bindings: &HashMap<String, String>) -> Result<(), String> {
+    app.global_shortcut().unregister_all().map_err(|e| e.to_string())?;
+
+    for (action, accelerator) in bindings {
+        match parse_accelerator(accelerator) {
+            Ok(shortcut) => {
+                if let Err(e) = app.global_shortcut().register(shortcut) {
+                    println!("Failed to register shortcut \"{}\" for {}: {}", accelerator, action, e);
+                }
+            }
+            Err(e) => println!("Skipping shortcut for {}: {}", action, e),
+        }
+    }
+
+    Ok(())
+}
+
+// Runs the effect bound to a fired shortcut's action.
+fn dispatch_shortcut_action(app: &tauri::AppHandle, action: &str) {
+    let app = app.clone();
+    let action = action.to_string();
+    tauri::async_runtime::spawn(async move {
+        match action.as_str() {
+            "toggle_overlay" => {
+                let state = app.state::<OverlayState>();
+                let _ = toggle_overlay(app.clone(), state).await;
+            }
+            "toggle_click_through" => {
+                let state = app.state::<OverlayState>();
+                let enabled = !*state.is_click_through.lock().unwrap();
+                let _ = set_click_through(app.clone(), state, enabled).await;
+            }
+            "hide" => {
+                let state = app.state::<OverlayState>();
+                let _ = hide_overlay(app.clone(), state).await;
+            }
+            "new_conversation" => {
+                let state = app.state::<ConversationState>();
+                let _ = clear_conversation(state).await;
+            }
+            _ => {}
+        }
+    });
+}
+
+// Command to rebind a named action to a new accelerator. Unregisters the
+// action's previous binding (if any) and registers the new one, rejecting
+// accelerators that are invalid or already bound to a different action.
+#[tauri::command]
+async fn set_shortcut(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ShortcutState>,
+    action: String,
+    accelerator: String,
+) -> Result<(), String> {
+    if !SHORTCUT_ACTIONS.contains(&action.as_str()) {
+        return Err(format!("Unknown shortcut action \"{}\"", action));
+    }
+
+    let shortcut = parse_accelerator(&accelerator)?;
+
+    let mut bindings = state.bindings.lock().unwrap();
+    if let Some((conflicting_action, _)) = bindings.iter().find(|(other_action, other_accelerator)| {
+        other_action.as_str() != action
+            && parse_accelerator(other_accelerator).map(|s| s == shortcut).unwrap_or(false)
+    }) {
+        return Err(format!("\"{}\" is already bound to \"{}\"", accelerator, conflicting_action));
+    }
+
+    let previous_shortcut = bindings.get(&action).and_then(|previous| parse_accelerator(previous).ok());
+    if let Some(previous_shortcut) = previous_shortcut {
+        let _ = app.global_shortcut().unregister(previous_shortcut);
+    }
+
+    if let Err(e) = app.global_shortcut().register(shortcut) {
+        // Restore the previous binding so the plugin still matches `bindings`.
+        if let Some(previous_shortcut) = previous_shortcut {
+            let _ = app.global_shortcut().register(previous_shortcut);
+        }
+        return Err(format!("Failed to register \"{}\": {}", accelerator, e));
+    }
+
+    bindings.insert(action, accelerator);
+    save_shortcuts(&app, &bindings)?;
+    Ok(())
+}
+
+// Command to read back the current action -> accelerator bindings.
+#[tauri::command]
+async fn get_shortcuts(state: tauri::State<'_, ShortcutState>) -> Result<HashMap<String, String>, String> {
+    Ok(state.bindings.lock().unwrap().clone())
 }
 
 // Command to toggle overlay visibility
@@ -25,6 +188,10 @@ async fn toggle_overlay(
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
         set_click_through_internal(&window, false)?;
+        *state.is_click_through.lock().unwrap() = false;
+        // Re-apply, since some platforms drop this attribute on show/hide.
+        let visible_on_all_workspaces = *state.visible_on_all_workspaces.lock().unwrap();
+        apply_visible_on_all_workspaces(&window, visible_on_all_workspaces)?;
     } else {
         // Hide window
         window.hide().map_err(|e| e.to_string())?;
@@ -36,12 +203,15 @@ async fn toggle_overlay(
 #[tauri::command]
 async fn set_click_through(
     app: tauri::AppHandle,
+    state: tauri::State<'_, OverlayState>,
     enabled: bool,
 ) -> Result<(), String> {
     let window = app.get_webview_window("main")
         .ok_or("Failed to get main window")?;
-    
-    set_click_through_internal(&window, enabled)
+
+    set_click_through_internal(&window, enabled)?;
+    *state.is_click_through.lock().unwrap() = enabled;
+    Ok(())
 }
 
 // Internal function to set click-through using Windows API
@@ -74,7 +244,84 @@ fn set_click_through_internal(
             SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
         }
     }
-    
+
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::msg_send;
+        use objc2::runtime::AnyObject;
+
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as *mut AnyObject;
+        unsafe {
+            let _: () = msg_send![ns_window, setIgnoresMouseEvents: enabled];
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+        use gtk::cairo::Region;
+
+        let gtk_window = window.gtk_window().map_err(|e| e.to_string())?;
+        let gdk_window = gtk_window.window()
+            .ok_or("GTK window has no backing GdkWindow yet")?;
+
+        if enabled {
+            // An empty input shape lets every mouse event fall through to
+            // whatever is behind the window.
+            gdk_window.input_shape_combine_region(Some(&Region::create()), 0, 0);
+        } else {
+            // Clearing the shape (rather than setting a rectangle sized from
+            // the current allocation) restores GTK's default resize-aware
+            // full-window input acceptance, so a later resize can't leave
+            // part of the window stuck non-clickable.
+            gdk_window.input_shape_combine_region(None, 0, 0);
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (window, enabled);
+        return Err("Click-through is not supported on this platform".to_string());
+    }
+
+    Ok(())
+}
+
+// Command to keep (or stop keeping) the overlay visible when the user
+// switches virtual desktops/workspaces.
+#[tauri::command]
+async fn set_visible_on_all_workspaces(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, OverlayState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let window = app.get_webview_window("main")
+        .ok_or("Failed to get main window")?;
+
+    apply_visible_on_all_workspaces(&window, enabled)?;
+    *state.visible_on_all_workspaces.lock().unwrap() = enabled;
+    Ok(())
+}
+
+// Applies the all-workspaces preference to the window. Tauri's own
+// `set_visible_on_all_workspaces` covers macOS (`NSWindowCollectionBehaviorCanJoinAllSpaces`)
+// and Linux, and is a no-op on Windows. Windows has no per-window "all
+// desktops" flag of its own: `HWND_TOPMOST` only changes z-order on the
+// *current* virtual desktop, it doesn't assign the window to every desktop,
+// so it's not a substitute. The only mechanism that actually does this is
+// the undocumented `IVirtualDesktopPinnedApps` COM interface, which isn't
+// stable across Windows versions, so rather than fake support with
+// something that silently doesn't work, report it honestly as unsupported.
+fn apply_visible_on_all_workspaces(window: &tauri::WebviewWindow, enabled: bool) -> Result<(), String> {
+    window.set_visible_on_all_workspaces(enabled).map_err(|e| e.to_string())?;
+
+    #[cfg(target_os = "windows")]
+    {
+        if enabled {
+            return Err("Keeping a window visible on all virtual desktops is not supported on Windows".to_string());
+        }
+    }
+
     Ok(())
 }
 
@@ -84,14 +331,29 @@ struct GeminiRequest {
     contents: Vec<GeminiContent>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct GeminiContent {
+    role: String,
     parts: Vec<GeminiPart>,
 }
 
-#[derive(serde::Serialize)]
-struct GeminiPart {
-    text: String,
+// A content part sent to Gemini: either plain text or an inline image
+// (base64-encoded bytes plus its MIME type), per the `generateContent` API.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum GeminiPart {
+    Text { text: String },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: GeminiInlineData,
+    },
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct GeminiInlineData {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    data: String,
 }
 
 #[derive(serde::Deserialize)]
@@ -181,17 +443,66 @@ async fn get_best_available_model(api_key: &str) -> Result<(String, String), Str
     Ok(("gemini-1.5-flash".to_string(), "v1beta".to_string()))
 }
 
-// Command to send request to AI
+// Monotonically increasing id used to correlate a streamed response's
+// "ai-chunk"/"ai-done" events with the request that started it.
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone, serde::Serialize)]
+struct AiChunkEvent {
+    request_id: String,
+    delta: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct AiDoneEvent {
+    request_id: String,
+    error: Option<String>,
+}
+
+// Command to send request to AI. Returns immediately with a request id;
+// the response is streamed to the frontend as "ai-chunk"/"ai-done" events.
+// The prompt is appended to the running conversation so the whole history
+// (not just this one turn) is sent to Gemini, and the reply is appended
+// back once the stream completes.
 #[tauri::command]
-async fn send_to_ai(prompt: String, model: String) -> Result<String, String> {
+async fn send_to_ai(
+    app: tauri::AppHandle,
+    conversation: tauri::State<'_, ConversationState>,
+    prompt: String,
+    model: String,
+) -> Result<String, String> {
+    let (contents, generation) = {
+        let mut history = conversation.history.lock().unwrap();
+        history.push(GeminiContent {
+            role: "user".to_string(),
+            parts: vec![GeminiPart::Text { text: prompt }],
+        });
+        (history.clone(), *conversation.generation.lock().unwrap())
+    };
+
+    begin_gemini_stream(app, model, contents, generation).await
+}
+
+// Resolves the model to use, then spawns the streaming request and returns
+// the request id immediately so the caller doesn't block on the full reply.
+// Shared by every command that turns a `Vec<GeminiContent>` into a streamed
+// "ai-chunk"/"ai-done" exchange. `generation` is the conversation's
+// generation at dispatch time, so the completion handler can tell whether
+// `clear_conversation` reset the history while this stream was in flight.
+async fn begin_gemini_stream(
+    app: tauri::AppHandle,
+    model: String,
+    contents: Vec<GeminiContent>,
+    generation: u64,
+) -> Result<String, String> {
     // Check for API Key
     let api_key = std::env::var("GEMINI_API_KEY")
         .map_err(|_| "GEMINI_API_KEY environment variable not set. Please check your .env file.".to_string())?;
-    let api_key = api_key.trim(); 
+    let api_key = api_key.trim().to_string();
 
     // Dynamic Discovery: Get the best available model for this key
     let (model_name, api_version) = if model == "gemini" {
-        get_best_available_model(api_key).await?
+        get_best_available_model(&api_key).await?
     } else {
         // Allow manual override if they specifically asked for something else
         (model, "v1beta".to_string())
@@ -199,22 +510,53 @@ async fn send_to_ai(prompt: String, model: String) -> Result<String, String> {
 
     println!("Using Auto-Selected Model: {} (API Version: {})", model_name, api_version);
 
+    let request_id = NEXT_STREAM_ID.fetch_add(1, Ordering::SeqCst).to_string();
+    let stream_request_id = request_id.clone();
+
+    tauri::async_runtime::spawn(async move {
+        match stream_gemini_response(&app, &stream_request_id, &api_key, &api_version, &model_name, contents).await {
+            Ok(full_text) => {
+                let conversation = app.state::<ConversationState>();
+                // Hold `history`'s lock across the generation re-check so a
+                // `clear_conversation` can't slip in between the check and
+                // the push and have this stale reply land on the fresh history.
+                let mut history = conversation.history.lock().unwrap();
+                if *conversation.generation.lock().unwrap() == generation {
+                    history.push(GeminiContent {
+                        role: "model".to_string(),
+                        parts: vec![GeminiPart::Text { text: full_text }],
+                    });
+                }
+                drop(history);
+                let _ = app.emit("ai-done", AiDoneEvent { request_id: stream_request_id, error: None });
+            }
+            Err(error) => {
+                let _ = app.emit("ai-done", AiDoneEvent { request_id: stream_request_id, error: Some(error) });
+            }
+        }
+    });
+
+    Ok(request_id)
+}
+
+// Reads the `streamGenerateContent` SSE stream line by line, forwarding each
+// text delta to the frontend as it arrives, and returns the full reply text
+// on success so the caller can append it to the conversation history.
+async fn stream_gemini_response(
+    app: &tauri::AppHandle,
+    request_id: &str,
+    api_key: &str,
+    api_version: &str,
+    model_name: &str,
+    contents: Vec<GeminiContent>,
+) -> Result<String, String> {
     let client = reqwest::Client::new();
     let url = format!(
-        "https://generativelanguage.googleapis.com/{}/models/{}:generateContent?key={}",
+        "https://generativelanguage.googleapis.com/{}/models/{}:streamGenerateContent?alt=sse&key={}",
         api_version, model_name, api_key
     );
 
-    // Debug log
-    println!("Requesting URL: https://generativelanguage.googleapis.com/{}/models/{}:generateContent?key=MASKED", api_version, model_name);
-
-    let request_body = GeminiRequest {
-        contents: vec![GeminiContent {
-            parts: vec![GeminiPart {
-                text: prompt,
-            }],
-        }],
-    };
+    let request_body = GeminiRequest { contents };
 
     let res = client.post(&url)
         .json(&request_body)
@@ -228,21 +570,206 @@ async fn send_to_ai(prompt: String, model: String) -> Result<String, String> {
         return Err(format!("API Error ({}): {}", status, error_text));
     }
 
-    let response_json: GeminiResponse = res.json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    let mut stream = res.bytes_stream();
+    let mut buffer = String::new();
+    let mut full_text = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(newline) = buffer.find('\n') {
+            let line = buffer[..newline].trim_end().to_string();
+            buffer.drain(..=newline);
+
+            let Some(data) = line.strip_prefix("data: ") else {
+                continue;
+            };
+            if data.is_empty() {
+                continue;
+            }
+
+            if let Some(delta) = parse_sse_delta(data)? {
+                full_text.push_str(&delta);
+                app.emit("ai-chunk", AiChunkEvent {
+                    request_id: request_id.to_string(),
+                    delta,
+                }).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    Ok(full_text)
+}
+
+// Command to clear the running conversation, starting a fresh context.
+#[tauri::command]
+async fn clear_conversation(conversation: tauri::State<'_, ConversationState>) -> Result<(), String> {
+    // Bump the generation while still holding `history`'s lock, so a stream
+    // completing in between can't push its stale reply onto the cleared history.
+    let mut history = conversation.history.lock().unwrap();
+    history.clear();
+    *conversation.generation.lock().unwrap() += 1;
+    Ok(())
+}
+
+// Command to read back the running conversation so the overlay can render it.
+#[tauri::command]
+async fn get_history(conversation: tauri::State<'_, ConversationState>) -> Result<Vec<GeminiContent>, String> {
+    Ok(conversation.history.lock().unwrap().clone())
+}
+
+// A sub-rectangle of the primary monitor, in screen coordinates. `None` in
+// `capture_and_ask` captures the whole monitor instead.
+#[derive(serde::Deserialize)]
+struct CaptureRect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+// Command to screenshot what's behind the overlay and ask Gemini about it.
+// Relies on the overlay already being excluded from capture (see
+// `WDA_EXCLUDEFROMCAPTURE` in `run`'s `setup`), so the grab only picks up
+// whatever is underneath it.
+#[tauri::command]
+async fn capture_and_ask(
+    app: tauri::AppHandle,
+    conversation: tauri::State<'_, ConversationState>,
+    prompt: String,
+    model: String,
+    rect: Option<CaptureRect>,
+) -> Result<String, String> {
+    let png_bytes = capture_screen_region(rect)?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+
+    let (contents, generation) = {
+        let mut history = conversation.history.lock().unwrap();
+        history.push(GeminiContent {
+            role: "user".to_string(),
+            parts: vec![
+                GeminiPart::Text { text: prompt },
+                GeminiPart::InlineData {
+                    inline_data: GeminiInlineData {
+                        mime_type: "image/png".to_string(),
+                        data: encoded,
+                    },
+                },
+            ],
+        });
+        (history.clone(), *conversation.generation.lock().unwrap())
+    };
+
+    begin_gemini_stream(app, model, contents, generation).await
+}
 
-    if let Some(error) = response_json.error {
+// Grabs a region of the primary monitor as PNG bytes. `rect` selects a
+// sub-rectangle in screen coordinates; `None` captures the whole monitor.
+// Only Windows has a capture path; on other platforms this returns an error.
+fn capture_screen_region(rect: Option<CaptureRect>) -> Result<Vec<u8>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::Graphics::Gdi::{
+            BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject,
+            GetDC, GetDIBits, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER,
+            BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+        let (x, y, width, height) = match rect {
+            Some(r) => (r.x, r.y, r.width, r.height),
+            None => unsafe { (0, 0, GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) },
+        };
+
+        if width <= 0 || height <= 0 {
+            return Err("Capture rectangle must have a positive width and height".to_string());
+        }
+
+        unsafe {
+            let screen_dc = GetDC(HWND(0));
+            let mem_dc = CreateCompatibleDC(screen_dc);
+            let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+            let old_bitmap = SelectObject(mem_dc, bitmap);
+
+            let blit_ok = BitBlt(mem_dc, 0, 0, width, height, screen_dc, x, y, SRCCOPY).is_ok();
+
+            let mut bitmap_info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    biHeight: -height, // negative = top-down DIB, matching screen order
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let mut pixels = vec![0u8; (width * height * 4) as usize];
+            if blit_ok {
+                GetDIBits(
+                    mem_dc,
+                    bitmap,
+                    0,
+                    height as u32,
+                    Some(pixels.as_mut_ptr() as *mut _),
+                    &mut bitmap_info,
+                    DIB_RGB_COLORS,
+                );
+            }
+
+            SelectObject(mem_dc, old_bitmap);
+            let _ = DeleteObject(bitmap);
+            let _ = DeleteDC(mem_dc);
+            ReleaseDC(HWND(0), screen_dc);
+
+            if !blit_ok {
+                return Err("Failed to copy screen contents into the capture buffer".to_string());
+            }
+
+            // BGRA -> RGBA
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+
+            let image = image::RgbaImage::from_raw(width as u32, height as u32, pixels)
+                .ok_or("Failed to assemble captured image")?;
+
+            let mut png_bytes = Vec::new();
+            image::DynamicImage::ImageRgba8(image)
+                .write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)
+                .map_err(|e| e.to_string())?;
+
+            Ok(png_bytes)
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = rect;
+        Err("Screen capture is not yet implemented on this platform".to_string())
+    }
+}
+
+// Parses one `data: {...}` SSE line into the text delta it carries, if any.
+fn parse_sse_delta(data: &str) -> Result<Option<String>, String> {
+    let response: GeminiResponse = serde_json::from_str(data)
+        .map_err(|e| format!("Failed to parse stream chunk: {}", e))?;
+
+    if let Some(error) = response.error {
         return Err(format!("Gemini API Error: {}", error.message));
     }
 
-    if let Some(candidates) = response_json.candidates {
+    if let Some(candidates) = response.candidates {
         if let Some(candidate) = candidates.first() {
             if let Some(content) = &candidate.content {
                 if let Some(parts) = &content.parts {
                     if let Some(part) = parts.first() {
                         if let Some(text) = &part.text {
-                            return Ok(text.clone());
+                            return Ok(Some(text.clone()));
                         }
                     }
                 }
@@ -250,7 +777,7 @@ async fn send_to_ai(prompt: String, model: String) -> Result<String, String> {
         }
     }
 
-    Err("No valid response text found in API response.".to_string())
+    Ok(None)
 }
 
 // Command to hide overlay (for close button)
@@ -276,14 +803,43 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state != tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        return;
+                    }
+                    let state = app.state::<ShortcutState>();
+                    let action = state.bindings.lock().unwrap().iter().find_map(|(action, accelerator)| {
+                        (parse_accelerator(accelerator).as_ref() == Ok(shortcut)).then(|| action.clone())
+                    });
+                    if let Some(action) = action {
+                        dispatch_shortcut_action(app, &action);
+                    }
+                })
+                .build(),
+        )
         .manage(OverlayState {
             is_visible: Mutex::new(false),
+            is_click_through: Mutex::new(false),
+            // Windows can't actually honor this (see apply_visible_on_all_workspaces),
+            // so default it off there instead of claiming a preference we can't apply.
+            visible_on_all_workspaces: Mutex::new(!cfg!(target_os = "windows")),
+        })
+        .manage(ConversationState {
+            history: Mutex::new(Vec::new()),
+            generation: Mutex::new(0),
         })
         .invoke_handler(tauri::generate_handler![
             toggle_overlay,
             set_click_through,
             send_to_ai,
+            clear_conversation,
+            get_history,
+            capture_and_ask,
+            set_shortcut,
+            get_shortcuts,
+            set_visible_on_all_workspaces,
             hide_overlay,
         ])
         .setup(|app| {
@@ -325,21 +881,76 @@ pub fn run() {
                     let _ = window.center();
                 }
             }
-            
-            // Register global shortcut (Ctrl + Space)
+
+            // Keep the always-available assistant present across virtual
+            // desktops/workspaces by default, where the platform can honor it.
+            apply_visible_on_all_workspaces(&window, !cfg!(target_os = "windows"))?;
+
+            // Tray icon: visible entry point when the overlay is hidden,
+            // kept in sync with the global shortcut through OverlayState.
+            {
+                use tauri::menu::{Menu, MenuItem};
+                use tauri::tray::TrayIconBuilder;
+
+                let show_hide_item = MenuItem::with_id(app, "show_hide", "Show/Hide Overlay", true, None::<&str>)?;
+                let click_through_item = MenuItem::with_id(app, "toggle_click_through", "Toggle Click-Through", true, None::<&str>)?;
+                let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+                let tray_menu = Menu::with_items(app, &[&show_hide_item, &click_through_item, &quit_item])?;
+
+                TrayIconBuilder::new()
+                    .icon(app.default_window_icon().unwrap().clone())
+                    .menu(&tray_menu)
+                    .show_menu_on_left_click(false)
+                    .on_menu_event(|app, event| match event.id.as_ref() {
+                        "show_hide" => {
+                            let app_clone = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                let state = app_clone.state::<OverlayState>();
+                                let app_for_toggle = app_clone.clone();
+                                let _ = toggle_overlay(app_for_toggle, state).await;
+                            });
+                        }
+                        "toggle_click_through" => {
+                            let app_clone = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                let state = app_clone.state::<OverlayState>();
+                                let enabled = !*state.is_click_through.lock().unwrap();
+                                let _ = set_click_through(app_clone.clone(), state, enabled).await;
+                            });
+                        }
+                        "quit" => app.exit(0),
+                        _ => {}
+                    })
+                    .on_tray_icon_event(|tray, event| {
+                        if let tauri::tray::TrayIconEvent::Click {
+                            button: tauri::tray::MouseButton::Left,
+                            button_state: tauri::tray::MouseButtonState::Up,
+                            ..
+                        } = event
+                        {
+                            let app = tray.app_handle().clone();
+                            tauri::async_runtime::spawn(async move {
+                                let state = app.state::<OverlayState>();
+                                let app_for_toggle = app.clone();
+                                let _ = toggle_overlay(app_for_toggle, state).await;
+                            });
+                        }
+                    })
+                    .build(app)?;
+            }
+
+            // Load the user's persisted shortcut bindings (or the defaults
+            // if this is a fresh install), manage the state, and only then
+            // register them with the OS — the plugin's `with_handler`
+            // closure reads `ShortcutState` and can fire as soon as a
+            // shortcut is registered, so the state must exist first.
             let app_handle = app.handle().clone();
-            
-            app.global_shortcut().on_shortcut("Ctrl+Space", move |_app, _shortcut, event| {
-                if event.state == tauri_plugin_global_shortcut::ShortcutState::Pressed {
-                    let app_clone = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
-                        let state = app_clone.state::<OverlayState>();
-                        let app_for_toggle = app_clone.clone();
-                        let _ = toggle_overlay(app_for_toggle, state).await;
-                    });
-                }
-            })?;
-            
+            let bindings = load_shortcuts(&app_handle);
+            app.manage(ShortcutState {
+                bindings: Mutex::new(bindings.clone()),
+            });
+            register_all_shortcuts(&app_handle, &bindings)?;
+
             Ok(())
         })
         .run(tauri::generate_context!())